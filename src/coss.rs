@@ -17,12 +17,48 @@
  */
 
 use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
-use std::time::SystemTime;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 static COSS_API_BASE_URL: &str = "https://trade.coss.io";
 
+// Used until `Client::load_rate_limits` has fetched the exchange's
+// declared limits; conservative enough not to get a fresh client throttled
+// immediately.
+const DEFAULT_RATE_LIMIT: u32 = 1200;
+const DEFAULT_RATE_LIMIT_INTERVAL_SECS: f64 = 60.0;
+
+const MAX_RETRIES: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+pub(crate) mod stream;
+
+/// Serde helper to read/write the exchange's string-encoded decimal fields
+/// as `rust_decimal::Decimal` instead of losing precision through `f32`.
+pub(crate) mod decimal_string {
+    use rust_decimal::Decimal;
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<Decimal>().map_err(D::Error::custom)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub(crate) struct Credentials {
     pub public_key: String,
@@ -34,9 +70,12 @@ pub(crate) struct Credentials {
 pub(crate) struct Asset {
     pub currency_code: Option<String>,
     pub address: Option<String>,
-    pub total: String,
-    pub available: String,
-    pub in_order: String,
+    #[serde(with = "decimal_string")]
+    pub total: Decimal,
+    #[serde(with = "decimal_string")]
+    pub available: Decimal,
+    #[serde(with = "decimal_string")]
+    pub in_order: Decimal,
     pub memo: Option<String>,
     pub memoLabel: Option<String>,
 }
@@ -44,7 +83,8 @@ pub(crate) struct Asset {
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct Price {
     pub symbol: String,
-    pub price: String,
+    #[serde(with = "decimal_string")]
+    pub price: Decimal,
     pub updated_time: u64,
 }
 
@@ -68,11 +108,16 @@ pub(crate) struct OrderResponse {
     pub status: OrderStatus,
     pub createTime: u64,
     pub r#type: String,
-    pub order_price: String,
-    pub order_size: String,
-    pub executed: String,
-    pub stop_price: String,
-    pub avg: String,
+    #[serde(with = "decimal_string")]
+    pub order_price: Decimal,
+    #[serde(with = "decimal_string")]
+    pub order_size: Decimal,
+    #[serde(with = "decimal_string")]
+    pub executed: Decimal,
+    #[serde(with = "decimal_string")]
+    pub stop_price: Decimal,
+    #[serde(with = "decimal_string")]
+    pub avg: Decimal,
 }
 
 #[allow(non_snake_case)]
@@ -87,12 +132,18 @@ pub(crate) struct OrderAddResponse {
     pub createTime: u64,
     pub r#type: String,
     pub timeMatching: u64,
-    pub order_price: String,
-    pub order_size: String,
-    pub executed: String,
-    pub stop_price: String,
-    pub avg: String,
-    pub total: String,
+    #[serde(with = "decimal_string")]
+    pub order_price: Decimal,
+    #[serde(with = "decimal_string")]
+    pub order_size: Decimal,
+    #[serde(with = "decimal_string")]
+    pub executed: Decimal,
+    #[serde(with = "decimal_string")]
+    pub stop_price: Decimal,
+    #[serde(with = "decimal_string")]
+    pub avg: Decimal,
+    #[serde(with = "decimal_string")]
+    pub total: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -101,23 +152,217 @@ pub(crate) struct CancelOrderResponse {
     pub order_symbol: String,
 }
 
+/// Per-pair trading rules, analogous to Binance's `exchangeInfo` symbol
+/// filters: how finely price and quantity can be expressed, and the
+/// smallest order value the exchange will accept.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct SymbolInfo {
+    pub symbol: String,
+    pub base_asset_precision: u32,
+    pub quote_precision: u32,
+    #[serde(with = "decimal_string")]
+    pub tick_size: Decimal,
+    #[serde(with = "decimal_string")]
+    pub step_size: Decimal,
+    #[serde(with = "decimal_string")]
+    pub min_notional: Decimal,
+}
+
+impl SymbolInfo {
+    /// Rounds a quantity down to the nearest `step_size` (lot size).
+    pub fn round_size(&self, size: Decimal) -> Decimal {
+        round_down_to_step(size, self.step_size)
+    }
+
+    /// Rounds a price to the nearest `tick_size`.
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        if self.tick_size.is_zero() {
+            return price;
+        }
+
+        (price / self.tick_size).round() * self.tick_size
+    }
+
+    /// Whether `size * price` meets this pair's minimum order value.
+    pub fn meets_min_notional(&self, size: Decimal, price: Decimal) -> bool {
+        size * price >= self.min_notional
+    }
+}
+
+fn round_down_to_step(value: Decimal, step: Decimal) -> Decimal {
+    if step.is_zero() {
+        return value;
+    }
+
+    (value / step).trunc() * step
+}
+
+/// One of the exchange's declared request-weight limits, analogous to
+/// Binance's `rate_limits`/`RateLimit`.
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct RateLimit {
+    pub rate_limit_type: String,
+    pub interval: String,
+    pub interval_num: u32,
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ExchangeInfo {
+    pub symbols: Vec<SymbolInfo>,
+    pub rate_limits: Vec<RateLimit>,
+}
+
+fn interval_to_secs(interval: &str, interval_num: u32) -> f64 {
+    let unit_secs = match interval {
+        "SECOND" => 1.0,
+        "MINUTE" => 60.0,
+        "HOUR" => 3600.0,
+        "DAY" => 86400.0,
+        _ => DEFAULT_RATE_LIMIT_INTERVAL_SECS,
+    };
+
+    unit_secs * interval_num as f64
+}
+
+/// A token-bucket limiter keyed off the exchange's declared request
+/// weight, so a bot polling many endpoints per tick backs off on its own
+/// instead of relying on the exchange to throttle it with a 429.
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: u32, interval_secs: f64) -> TokenBucket {
+        let capacity = limit as f64;
+        TokenBucket {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / interval_secs,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn configure(&mut self, limit: u32, interval_secs: f64) {
+        self.capacity = limit as f64;
+        self.refill_per_sec = self.capacity / interval_secs;
+        self.tokens = self.tokens.min(self.capacity);
+    }
+
+    fn refill(&mut self) {
+        let elapsed = self.last_refill.elapsed().as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = Instant::now();
+    }
+
+    /// Blocks until `weight` tokens are available, then spends them.
+    fn acquire(&mut self, weight: u32) {
+        let weight = weight as f64;
+        loop {
+            self.refill();
+            if self.tokens >= weight {
+                self.tokens -= weight;
+                return;
+            }
+            let deficit = weight - self.tokens;
+            let wait_secs = (deficit / self.refill_per_sec).max(0.001);
+            thread::sleep(Duration::from_secs_f64(wait_secs));
+        }
+    }
+}
+
+/// Errors surfaced by [`Client`], distinguishing throttling from auth or
+/// parse failures so callers can react differently (e.g. back off vs. bail
+/// out).
+#[derive(Debug)]
+pub(crate) enum ClientError {
+    Http(reqwest::Error),
+    RateLimited { retry_after: Option<Duration> },
+    Unauthorized(String),
+    Parse(serde_json::error::Error),
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Http(e) => write!(f, "{}", e),
+            ClientError::RateLimited { retry_after } => match retry_after {
+                Some(d) => write!(f, "rate limited, retry after {:?}", d),
+                None => write!(f, "rate limited"),
+            },
+            ClientError::Unauthorized(msg) => write!(f, "{}", msg),
+            ClientError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Http(e)
+    }
+}
+
+impl From<serde_json::error::Error> for ClientError {
+    fn from(e: serde_json::error::Error) -> Self {
+        ClientError::Parse(e)
+    }
+}
+
+/// Errors returned by [`Client::add_order`] and [`Client::add_market_order`].
+#[derive(Debug)]
+pub(crate) enum OrderError {
+    /// `size * price` fell below the pair's `min_notional`.
+    BelowMinNotional { notional: Decimal, min_notional: Decimal },
+    Client(ClientError),
+}
+
+impl std::fmt::Display for OrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::BelowMinNotional { notional, min_notional } => write!(
+                f,
+                "order value {} is below the minimum notional of {}",
+                notional, min_notional
+            ),
+            OrderError::Client(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+impl From<ClientError> for OrderError {
+    fn from(e: ClientError) -> Self {
+        OrderError::Client(e)
+    }
+}
+
+impl From<serde_json::error::Error> for OrderError {
+    fn from(e: serde_json::error::Error) -> Self {
+        OrderError::Client(ClientError::Parse(e))
+    }
+}
+
+#[derive(Clone, Copy)]
 enum HttpRequest {
     GET,
     POST,
     DELETE,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum OrderSide {
     BUY,
     SELL,
 }
 
-pub(crate) enum OrderType {
-    MARKET,
-    LIMIT,
-}
-
-fn get_timestamp() -> String {
+pub(crate) fn get_timestamp() -> String {
     format!(
         "{}",
         SystemTime::now()
@@ -133,83 +378,137 @@ fn get_url(suffix: &str) -> String {
 
 pub(crate) struct Client {
     credentials: Credentials,
+    http: reqwest::Client,
+    limiter: Mutex<TokenBucket>,
 }
 
 impl Client {
     pub fn new(creds: Credentials) -> Client {
-        Client { credentials: creds }
+        Client {
+            credentials: creds,
+            http: reqwest::Client::new(),
+            limiter: Mutex::new(TokenBucket::new(
+                DEFAULT_RATE_LIMIT,
+                DEFAULT_RATE_LIMIT_INTERVAL_SECS,
+            )),
+        }
+    }
+
+    /// Fetches the exchange's declared request-weight limit and reconfigures
+    /// the rate limiter to match it. Call this once after constructing a
+    /// `Client`; until it's called, a conservative default is used.
+    pub fn load_rate_limits(&self) -> Result<(), ClientError> {
+        let limits = self.get_rate_limits()?;
+
+        if let Some(limit) = limits
+            .iter()
+            .find(|l| l.rate_limit_type == "REQUEST_WEIGHT")
+        {
+            self.limiter.lock().unwrap().configure(
+                limit.limit,
+                interval_to_secs(limit.interval.as_str(), limit.interval_num),
+            );
+        }
+
+        Ok(())
     }
 
+    // `build_to_sign` is re-run with a fresh timestamp on every attempt
+    // rather than once up front: COSS rejects a timestamp older than its
+    // `recvWindow` (see `get_orders`'s 5000ms), and with `MAX_RETRIES`
+    // backoff attempts spanning up to ~7.5s, a signature computed before
+    // the loop would be stale by the later retries.
     fn api_request(
         &self,
         req: HttpRequest,
         url: String,
-        to_sign: String,
-        mut params: Vec<(String, String)>,
-    ) -> Result<String, reqwest::Error> {
-        let mut mac = Hmac::<Sha256>::new_varkey(self.credentials.secret_key.as_bytes()).unwrap();
-        mac.input(to_sign.as_bytes());
-
-        let sig: Vec<String> = mac
-            .result()
-            .code()
-            .iter()
-            .map(|b| format!("{:02x}", b))
-            .collect();
-
-        params.push(("timestamp".to_string(), format!("{}", get_timestamp())));
-
-        match req {
-            HttpRequest::GET => Ok(reqwest::Client::new()
-                .get(url.as_str())
-                .header("Content-Type", "application/json")
-                .header("X-Requested-With", "XMLHttpRequest")
-                .header("Authorization", self.credentials.public_key.clone())
-                .header("Signature", sig.concat())
-                .query(&params)
-                .send()?
-                .text()?),
-            HttpRequest::POST => Ok(reqwest::Client::new()
-                .post(url.as_str())
-                .header("Content-Type", "application/json")
-                .header("X-Requested-With", "XMLHttpRequest")
-                .header("Authorization", self.credentials.public_key.clone())
-                .header("Signature", sig.concat())
-                .query(&params)
-                .body(to_sign)
-                .send()?
-                .text()?),
-            HttpRequest::DELETE => Ok(reqwest::Client::new()
-                .delete(url.as_str())
-                .header("Content-Type", "application/json")
-                .header("X-Requested-With", "XMLHttpRequest")
-                .header("Authorization", self.credentials.public_key.clone())
-                .header("Signature", sig.concat())
-                .query(&params)
-                .body(to_sign)
-                .send()?
-                .text()?),
+        build_to_sign: impl Fn(&str) -> String,
+        params: Vec<(String, String)>,
+        weight: u32,
+    ) -> Result<String, ClientError> {
+        let mut backoff = INITIAL_BACKOFF;
+
+        for attempt in 1..=MAX_RETRIES {
+            self.limiter.lock().unwrap().acquire(weight);
+
+            let timestamp = get_timestamp();
+            let to_sign = build_to_sign(timestamp.as_str());
+
+            let mut mac = Hmac::<Sha256>::new_varkey(self.credentials.secret_key.as_bytes()).unwrap();
+            mac.input(to_sign.as_bytes());
+
+            let sig: Vec<String> = mac
+                .result()
+                .code()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+
+            let mut attempt_params = params.clone();
+            attempt_params.push(("timestamp".to_string(), timestamp));
+
+            let mut builder = match req {
+                HttpRequest::GET => self.http.get(url.as_str()),
+                HttpRequest::POST => self.http.post(url.as_str()),
+                HttpRequest::DELETE => self.http.delete(url.as_str()),
+            }
+            .header("Content-Type", "application/json")
+            .header("X-Requested-With", "XMLHttpRequest")
+            .header("Authorization", self.credentials.public_key.clone())
+            .header("Signature", sig.concat())
+            .query(&attempt_params);
+
+            if !matches!(req, HttpRequest::GET) {
+                builder = builder.body(to_sign.clone());
+            }
+
+            let response = builder.send()?;
+            let status = response.status();
+
+            if status.as_u16() == 429 || status.as_u16() == 418 {
+                let retry_after = response
+                    .headers()
+                    .get("Retry-After")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .map(Duration::from_secs);
+
+                if attempt == MAX_RETRIES {
+                    return Err(ClientError::RateLimited { retry_after });
+                }
+
+                thread::sleep(retry_after.unwrap_or(backoff));
+                backoff *= 2;
+                continue;
+            }
+
+            if status.as_u16() == 401 || status.as_u16() == 403 {
+                return Err(ClientError::Unauthorized(format!(
+                    "request to {} was rejected with status {}",
+                    url, status
+                )));
+            }
+
+            return Ok(response.text()?);
         }
+
+        unreachable!("loop always returns or retries until MAX_RETRIES")
     }
 
-    pub fn get_balances(&self) -> Result<Vec<Asset>, serde_json::error::Error> {
-        let to_sign = format!("timestamp={}", get_timestamp());
-        let balances: Vec<Asset> = serde_json::from_str(
-            self.api_request(
-                HttpRequest::GET,
-                get_url("/c/api/v1/account/balances"),
-                to_sign,
-                vec![],
-            )
-            .unwrap()
-            .as_str(),
+    pub fn get_balances(&self) -> Result<Vec<Asset>, ClientError> {
+        let body = self.api_request(
+            HttpRequest::GET,
+            get_url("/c/api/v1/account/balances"),
+            |timestamp| format!("timestamp={}", timestamp),
+            vec![],
+            1,
         )?;
 
-        Ok(balances)
+        Ok(serde_json::from_str(body.as_str())?)
     }
 
-    pub fn get_balance(&self, coin: &str) -> Result<Asset, serde_json::error::Error> {
-        let balances = self.get_balances().unwrap();
+    pub fn get_balance(&self, coin: &str) -> Result<Asset, ClientError> {
+        let balances = self.get_balances()?;
         let asset: Asset = match balances.into_iter().find(|b| match &b.currency_code {
             Some(a) => a == coin,
             None => false,
@@ -221,36 +520,66 @@ impl Client {
         Ok(asset)
     }
 
-    pub fn get_available_balance(&self, coin: &str) -> f32 {
+    pub fn get_available_balance(&self, coin: &str) -> Decimal {
         match self.get_balance(coin) {
-            Ok(asset) => match asset.available.parse::<f32>() {
-                Ok(balance) => balance,
-                Err(_) => 0.0,
-            },
-            Err(_) => 0.0,
+            Ok(asset) => asset.available,
+            Err(_) => Decimal::ZERO,
         }
     }
 
-    pub fn get_market_price(&self, pair: &str) -> Result<f32, serde_json::error::Error> {
-        let to_sign = format!("timestamp={}", get_timestamp());
+    pub fn get_market_price(&self, pair: &str) -> Result<Decimal, ClientError> {
         let params: Vec<(String, String)> = vec![("symbol".to_string(), pair.to_string())];
-        let price: Vec<Price> = serde_json::from_str(
-            self.api_request(
-                HttpRequest::GET,
-                get_url("/c/api/v1/market-price"),
-                to_sign,
-                params,
-            )
-            .unwrap()
-            .as_str(),
+        let body = self.api_request(
+            HttpRequest::GET,
+            get_url("/c/api/v1/market-price"),
+            |timestamp| format!("timestamp={}", timestamp),
+            params,
+            1,
+        )?;
+
+        let price: Vec<Price> = serde_json::from_str(body.as_str())?;
+
+        Ok(price[0].price)
+    }
+
+    fn get_exchange_info(&self) -> Result<ExchangeInfo, ClientError> {
+        let body = self.api_request(
+            HttpRequest::GET,
+            get_url("/c/api/v1/exchange-info"),
+            |timestamp| format!("timestamp={}", timestamp),
+            vec![],
+            10,
         )?;
 
-        Ok(price[0].price.parse::<f32>().unwrap())
+        Ok(serde_json::from_str(body.as_str())?)
+    }
+
+    pub fn get_symbols(&self) -> Result<Vec<SymbolInfo>, ClientError> {
+        Ok(self.get_exchange_info()?.symbols)
+    }
+
+    pub fn get_rate_limits(&self) -> Result<Vec<RateLimit>, ClientError> {
+        Ok(self.get_exchange_info()?.rate_limits)
     }
 
-    pub fn get_orders(&self, pair: &str) -> Result<Vec<OrderResponse>, serde_json::error::Error> {
-        let to_sign: String = format!(
-            "
+    pub fn get_symbol_info(&self, pair: &str) -> Result<SymbolInfo, String> {
+        let symbols = self
+            .get_symbols()
+            .map_err(|e| format!("Failed to fetch exchange info: {}", e))?;
+
+        symbols
+            .into_iter()
+            .find(|s| s.symbol == pair)
+            .ok_or_else(|| format!("No symbol info found for pair {}", pair))
+    }
+
+    pub fn get_orders(&self, pair: &str) -> Result<Vec<OrderResponse>, ClientError> {
+        let body = self.api_request(
+            HttpRequest::POST,
+            get_url("/c/api/v1/order/list/all"),
+            |timestamp| {
+                format!(
+                    "
         {{
             \"symbol\": \"{}\"
             \"from_id\": null,
@@ -258,94 +587,84 @@ impl Client {
             \"recvWindow\": 5000,
             \"timestamp\": \"{}\"
         }}",
-            pair.to_string(),
-            get_timestamp()
-        );
-
-        let orders: Vec<OrderResponse> = serde_json::from_str(
-            self.api_request(
-                HttpRequest::POST,
-                get_url("/c/api/v1/order/list/all"),
-                to_sign,
-                vec![],
-            )
-            .unwrap()
-            .as_str(),
+                    pair, timestamp
+                )
+            },
+            vec![],
+            5,
         )?;
 
-        Ok(orders)
+        Ok(serde_json::from_str(body.as_str())?)
     }
 
-    pub fn get_order_details(
-        &self,
-        order_id: &str,
-    ) -> Result<OrderResponse, serde_json::error::Error> {
-        let to_sign: String = format!(
-            "
+    pub fn get_order_details(&self, order_id: &str) -> Result<OrderResponse, ClientError> {
+        let body = self.api_request(
+            HttpRequest::POST,
+            get_url("/c/api/v1/order/details"),
+            |timestamp| {
+                format!(
+                    "
         {{
             \"order_id\": \"{}\",
             \"timestamp\": \"{}\"
         }}",
-            order_id.to_string(),
-            get_timestamp()
-        );
-
-        println!("{}", to_sign);
-
-        let orders: OrderResponse = serde_json::from_str(
-            self.api_request(
-                HttpRequest::POST,
-                get_url("/c/api/v1/order/details"),
-                to_sign,
-                vec![],
-            )
-            .unwrap()
-            .as_str(),
+                    order_id, timestamp
+                )
+            },
+            vec![],
+            1,
         )?;
 
-        Ok(orders)
+        Ok(serde_json::from_str(body.as_str())?)
     }
 
+    /// Places a limit order. For market orders, which COSS sizes off an
+    /// amount rather than a tick price, use [`Client::add_market_order`].
     pub fn add_order(
         &self,
         pair: &str,
-        r#type: OrderType,
+        symbol: &SymbolInfo,
         side: OrderSide,
-        size: f32,
-        price: f32,
-    ) -> Result<OrderAddResponse, serde_json::error::Error> {
-        let to_sign: String = format!(
-            "
+        size: Decimal,
+        price: Decimal,
+    ) -> Result<OrderAddResponse, OrderError> {
+        let size = symbol.round_size(size);
+        let price = symbol.round_price(price);
+
+        if !symbol.meets_min_notional(size, price) {
+            return Err(OrderError::BelowMinNotional {
+                notional: size * price,
+                min_notional: symbol.min_notional,
+            });
+        }
+
+        let resp = self.api_request(
+            HttpRequest::POST,
+            get_url("/c/api/v1/order/add/"),
+            |timestamp| {
+                format!(
+                    "
         {{
             \"order_symbol\": \"{}\",
             \"order_side\": \"{}\",
-            \"type\": \"{}\",
-            \"order_size\": {:.3},
-            \"order_price\": {:.3},
+            \"type\": \"limit\",
+            \"order_size\": {},
+            \"order_price\": {},
             \"timestamp\": {}
         }}",
-            pair.to_string(),
-            match side {
-                OrderSide::BUY => "BUY",
-                _ => "SELL",
+                    pair,
+                    match side {
+                        OrderSide::BUY => "BUY",
+                        _ => "SELL",
+                    },
+                    size,
+                    price,
+                    timestamp
+                )
             },
-            match r#type {
-                OrderType::MARKET => "market",
-                _ => "limit",
-            },
-            size,
-            price,
-            get_timestamp()
-        );
-
-        let resp = self
-            .api_request(
-                HttpRequest::POST,
-                get_url("/c/api/v1/order/add/"),
-                to_sign,
-                vec![],
-            )
-            .unwrap();
+            vec![],
+            1,
+        )?;
 
         println!("{}", resp);
 
@@ -354,34 +673,70 @@ impl Client {
         Ok(response)
     }
 
-    pub fn cancel_order(
+    /// Places a market order. COSS's market endpoint settles off an amount
+    /// rather than a price, so `order_price` is omitted from the signed
+    /// body entirely instead of being filled in with a placeholder. `size`
+    /// is expressed in quote currency for a `BUY` (how much to spend) and
+    /// in base currency for a `SELL` (how much to sell) — callers must size
+    /// it accordingly.
+    pub fn add_market_order(
         &self,
         pair: &str,
-        id: &str,
-    ) -> Result<CancelOrderResponse, serde_json::error::Error> {
-        let to_sign: String = format!(
-            "
+        side: OrderSide,
+        size: Decimal,
+    ) -> Result<OrderAddResponse, OrderError> {
+        let resp = self.api_request(
+            HttpRequest::POST,
+            get_url("/c/api/v1/order/add/"),
+            |timestamp| {
+                format!(
+                    "
         {{
             \"order_symbol\": \"{}\",
-            \"order_id\": \"{}\",
+            \"order_side\": \"{}\",
+            \"type\": \"market\",
+            \"order_size\": {},
             \"timestamp\": {}
         }}",
-            pair.to_string(),
-            id,
-            get_timestamp()
-        );
-
-        let response: CancelOrderResponse = serde_json::from_str(
-            self.api_request(
-                HttpRequest::DELETE,
-                get_url("/c/api/v1/order/cancel"),
-                to_sign,
-                vec![],
-            )
-            .unwrap()
-            .as_str(),
+                    pair,
+                    match side {
+                        OrderSide::BUY => "BUY",
+                        _ => "SELL",
+                    },
+                    size,
+                    timestamp
+                )
+            },
+            vec![],
+            1,
         )?;
 
+        println!("{}", resp);
+
+        let response: OrderAddResponse = serde_json::from_str(resp.as_str())?;
+
         Ok(response)
     }
+
+    pub fn cancel_order(&self, pair: &str, id: &str) -> Result<CancelOrderResponse, ClientError> {
+        let body = self.api_request(
+            HttpRequest::DELETE,
+            get_url("/c/api/v1/order/cancel"),
+            |timestamp| {
+                format!(
+                    "
+        {{
+            \"order_symbol\": \"{}\",
+            \"order_id\": \"{}\",
+            \"timestamp\": {}
+        }}",
+                    pair, id, timestamp
+                )
+            },
+            vec![],
+            1,
+        )?;
+
+        Ok(serde_json::from_str(body.as_str())?)
+    }
 }
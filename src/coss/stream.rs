@@ -0,0 +1,156 @@
+/*
+ *
+ * Copyright 2019 Gregory Lemercier, All Rights Reserved.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing,
+ * software distributed under the License is distributed on an
+ * "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND,
+ * either express or implied. See the License for the specific
+ * language governing permissions and limitations under the License.
+ *
+ */
+
+use crate::coss::decimal_string;
+use crate::coss::{get_timestamp, Credentials};
+use futures::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use sha2::Sha256;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+static COSS_STREAM_URL: &str = "wss://wsapi.coss.io/web-stream/connect";
+
+// How long to wait before retrying a dropped connection.
+const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A fill-related event pushed by the exchange's authenticated user-data
+/// feed, used in place of polling `get_order_details` for every tracked
+/// order.
+#[derive(Debug, Clone)]
+pub(crate) enum OrderEvent {
+    OrderFilled {
+        order_id: String,
+        executed: Decimal,
+        avg: Decimal,
+    },
+    OrderPartiallyFilled {
+        order_id: String,
+        executed: Decimal,
+        avg: Decimal,
+    },
+    OrderCanceled {
+        order_id: String,
+    },
+    /// Emitted right after (re)subscribing, including the very first
+    /// connection. The caller should treat this as a cue to reconcile via
+    /// `process`, since fills may have occurred while the stream was down.
+    Reconnected,
+}
+
+#[derive(Deserialize, Debug)]
+struct RawUserDataEvent {
+    order_id: String,
+    event: String,
+    #[serde(with = "decimal_string")]
+    executed: Decimal,
+    #[serde(with = "decimal_string")]
+    avg: Decimal,
+}
+
+/// Opens the authenticated user-data stream for `pair` and spawns a task
+/// that forwards parsed fill/cancel events to the returned channel,
+/// reconnecting on drop. The caller (`Gridbot`) drives its replenish logic
+/// off this channel instead of polling `get_order_details`.
+pub(crate) fn connect(credentials: Credentials, pair: String) -> mpsc::UnboundedReceiver<OrderEvent> {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = run_once(&credentials, &pair, &tx).await {
+                eprintln!("user-data stream for {} dropped: {}", pair, e);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+
+    rx
+}
+
+async fn run_once(
+    credentials: &Credentials,
+    pair: &str,
+    tx: &mpsc::UnboundedSender<OrderEvent>,
+) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+    let (ws_stream, _) = tokio_tungstenite::connect_async(COSS_STREAM_URL).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    let timestamp = get_timestamp();
+    let to_sign = format!("timestamp={}", timestamp);
+
+    let mut mac = Hmac::<Sha256>::new_varkey(credentials.secret_key.as_bytes()).unwrap();
+    mac.input(to_sign.as_bytes());
+    let signature: String = mac
+        .result()
+        .code()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    let subscribe = serde_json::json!({
+        "method": "subscribe",
+        "params": {
+            "channel": format!("order:{}", pair),
+            "public_key": credentials.public_key,
+            "timestamp": timestamp,
+            "signature": signature,
+        },
+    });
+    write.send(Message::Text(subscribe.to_string())).await?;
+
+    // The consumer treats this as a prompt to reconcile over REST, since
+    // fills may have happened while this connection was down.
+    let _ = tx.send(OrderEvent::Reconnected);
+
+    while let Some(msg) = read.next().await {
+        let msg = msg?;
+        let text = match msg {
+            Message::Text(text) => text,
+            _ => continue,
+        };
+
+        let raw: RawUserDataEvent = match serde_json::from_str(&text) {
+            Ok(raw) => raw,
+            Err(_) => continue,
+        };
+
+        let event = match raw.event.as_str() {
+            "filled" => OrderEvent::OrderFilled {
+                order_id: raw.order_id,
+                executed: raw.executed,
+                avg: raw.avg,
+            },
+            "partial_fill" => OrderEvent::OrderPartiallyFilled {
+                order_id: raw.order_id,
+                executed: raw.executed,
+                avg: raw.avg,
+            },
+            "canceled" | "cancelling" => OrderEvent::OrderCanceled {
+                order_id: raw.order_id,
+            },
+            _ => continue,
+        };
+
+        // The receiving end only drops when the bot itself shuts down.
+        let _ = tx.send(event);
+    }
+
+    Ok(())
+}
@@ -16,23 +16,42 @@
  *
  */
 
-use crate::coss::{Client, OrderSide, OrderStatus, OrderType};
+use crate::coss::stream::{self, OrderEvent};
+use crate::coss::{Client, Credentials, OrderError, OrderSide, OrderStatus, SymbolInfo};
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use std::result::Result;
+use tokio::sync::mpsc;
 
 #[derive(Deserialize, Clone, Debug)]
 pub(crate) struct Configuration {
     pair: String,
-    upper_limit: f32,
-    lower_limit: f32,
-    order_amount: f32,
+    upper_limit: Decimal,
+    lower_limit: Decimal,
+    order_amount: Decimal,
     number_of_grids: u32,
 }
 
+/// A live order the bot is tracking, pinned to the grid level it was
+/// placed at so a fill can be replenished with a counter-order on the
+/// opposite side: one level up for a buy, one level down for a sell.
+struct GridOrder {
+    order_id: String,
+    level_index: usize,
+    side: OrderSide,
+}
+
 pub(crate) struct Gridbot {
     config: Configuration,
     client: Client,
-    order_ids: Vec<String>,
+    // Evenly spaced price levels between lower_limit and upper_limit,
+    // snapped to the pair's tick size.
+    levels: Vec<Decimal>,
+    orders: Vec<GridOrder>,
+    // Set by `connect_stream`; the real-time fill feed driving `run`.
+    // `process` remains the REST-poll fallback used to reconcile state
+    // after a reconnect.
+    events: Option<mpsc::UnboundedReceiver<OrderEvent>>,
 }
 
 impl Gridbot {
@@ -40,15 +59,128 @@ impl Gridbot {
         Gridbot {
             config: config,
             client: client,
-            order_ids: vec![],
+            levels: vec![],
+            orders: vec![],
+            events: None,
+        }
+    }
+
+    /// Opens the exchange's authenticated user-data stream for this pair.
+    /// Call once, after `initialize`, before driving the bot with `run`.
+    pub fn connect_stream(&mut self, credentials: Credentials) {
+        self.events = Some(stream::connect(credentials, self.config.pair.clone()));
+    }
+
+    /// Drives the grid off the real-time fill stream opened by
+    /// `connect_stream`, replenishing as soon as a fill event arrives
+    /// instead of waiting on the next `process` poll.
+    pub async fn run(&mut self) -> Result<(), String> {
+        let symbol = self.client.get_symbol_info(self.config.pair.as_str())?;
+
+        loop {
+            let event = self
+                .events
+                .as_mut()
+                .expect("connect_stream must be called before run")
+                .recv()
+                .await
+                .ok_or_else(|| "user-data stream closed".to_string())?;
+
+            self.handle_order_event(&symbol, event)?;
+        }
+    }
+
+    fn handle_order_event(&mut self, symbol: &SymbolInfo, event: OrderEvent) -> Result<(), String> {
+        match event {
+            OrderEvent::OrderFilled {
+                order_id,
+                executed,
+                avg,
+            } => {
+                let order_index = match self.orders.iter().position(|o| o.order_id == order_id) {
+                    Some(i) => i,
+                    None => return Ok(()),
+                };
+
+                println!("Order {} was filled: {} @ {}", order_id, executed, avg);
+                let order = self.orders.remove(order_index);
+
+                if let Some((level_index, side, price)) =
+                    self.next_counter_order(order.side, order.level_index)
+                {
+                    self.place_order(symbol, level_index, side, price, executed)
+                        .map_err(|e| format!("Failed to place {:?} counter-order at {}: {}", side, price, e))?;
+                }
+            }
+            OrderEvent::OrderPartiallyFilled { .. } => {
+                // Wait for the final `filled` event before replenishing.
+            }
+            OrderEvent::OrderCanceled { order_id } => {
+                self.orders.retain(|o| o.order_id != order_id);
+            }
+            OrderEvent::Reconnected => {
+                // process() is a blocking REST call chain (rate-limit waits,
+                // retry backoff); run it via block_in_place so it doesn't
+                // stall the websocket reader sharing this runtime.
+                tokio::task::block_in_place(|| self.process())?;
+            }
         }
+
+        Ok(())
+    }
+
+    /// The counter-order for a fill at `level_index, side`: on the opposite
+    /// side, one level up for a buy and one level down for a sell. `None` if
+    /// that level is outside the grid or already has a live order of the
+    /// counter side tracking it.
+    fn next_counter_order(&self, side: OrderSide, level_index: usize) -> Option<(usize, OrderSide, Decimal)> {
+        let (counter_side, counter_index) = match side {
+            OrderSide::BUY => (OrderSide::SELL, level_index + 1),
+            OrderSide::SELL if level_index > 0 => (OrderSide::BUY, level_index - 1),
+            OrderSide::SELL => return None,
+        };
+
+        let counter_price = *self.levels.get(counter_index)?;
+
+        let already_tracked = self
+            .orders
+            .iter()
+            .any(|o| o.level_index == counter_index && o.side == counter_side);
+
+        if already_tracked {
+            None
+        } else {
+            Some((counter_index, counter_side, counter_price))
+        }
+    }
+
+    fn place_order(
+        &mut self,
+        symbol: &SymbolInfo,
+        level_index: usize,
+        side: OrderSide,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<(), OrderError> {
+        let order = self
+            .client
+            .add_order(self.config.pair.as_str(), symbol, side, size, price)?;
+
+        println!("Placed {:?} order @ {}", side, price);
+        self.orders.push(GridOrder {
+            order_id: order.order_id,
+            level_index,
+            side,
+        });
+
+        Ok(())
     }
 
     pub fn initialize(&mut self) -> Result<(), String> {
         let coins: Vec<&str> = self.config.pair.split("_").collect();
 
         // Check config parameters
-        if self.config.upper_limit < 0.0 || self.config.lower_limit < 0.0 {
+        if self.config.upper_limit < Decimal::ZERO || self.config.lower_limit < Decimal::ZERO {
             return Err("Limits cannot be negative values".to_string());
         }
 
@@ -56,14 +188,24 @@ impl Gridbot {
             return Err("Upper limit must be higher than lower limit".to_string());
         }
 
+        if self.config.number_of_grids == 0 {
+            return Err("number_of_grids must be greater than zero".to_string());
+        }
+
+        // Replace the conservative default rate limit with the exchange's
+        // actual declared limit before making any further requests.
+        self.client
+            .load_rate_limits()
+            .map_err(|e| format!("Failed to load rate limits: {}", e))?;
+
         // Get current balance for each coin of the pai
-        let balances: Vec<f32> = coins
+        let balances: Vec<Decimal> = coins
             .iter()
             .map(|coin| self.client.get_available_balance(coin))
             .collect();
 
         // Get current market price
-        let current_price: f32 = self
+        let current_price: Decimal = self
             .client
             .get_market_price(self.config.pair.as_str())
             .unwrap();
@@ -76,26 +218,33 @@ impl Gridbot {
                 current_price));
         }
 
-        // Check balances are sufficient
-        let order_step: f32 = (self.config.upper_limit - self.config.lower_limit)
-            / self.config.number_of_grids as f32;
-        let num_sell_orders: u32 = ((self.config.upper_limit - current_price) / order_step) as u32;
-        let num_buy_orders: u32 = ((current_price - self.config.lower_limit) / order_step) as u32;
-
-        let mut required_sell_coins: f32 = 0.0;
-        let mut sell_orders: Vec<f32> = vec![];
-        for i in 1..(num_sell_orders + 1) {
-            let order = current_price + (i as f32 * order_step);
-            required_sell_coins += self.config.order_amount;
-            sell_orders.push(order);
+        // Fetch the pair's tick/lot/min-notional rules so grid levels are
+        // snapped to values the exchange will actually accept.
+        let symbol = self.client.get_symbol_info(self.config.pair.as_str())?;
+
+        if !symbol.meets_min_notional(self.config.order_amount, current_price) {
+            return Err(format!(
+                "order_amount {} at the current price of {} is below the minimum notional for {}",
+                self.config.order_amount, current_price, self.config.pair
+            ));
         }
 
-        let mut required_buy_coins: f32 = 0.0;
-        let mut buy_orders: Vec<f32> = vec![];
-        for i in 1..(num_buy_orders + 1) {
-            let order = current_price - (i as f32 * order_step);
-            required_buy_coins += self.config.order_amount * order;
-            buy_orders.push(order);
+        // Build the grid: number_of_grids + 1 evenly spaced price levels
+        // between lower_limit and upper_limit, snapped to the tick size.
+        let order_step: Decimal = (self.config.upper_limit - self.config.lower_limit)
+            / Decimal::from(self.config.number_of_grids);
+        self.levels = (0..=self.config.number_of_grids)
+            .map(|i| symbol.round_price(self.config.lower_limit + Decimal::from(i) * order_step))
+            .collect();
+
+        let mut required_sell_coins: Decimal = Decimal::ZERO;
+        let mut required_buy_coins: Decimal = Decimal::ZERO;
+        for level in &self.levels {
+            if *level > current_price {
+                required_sell_coins += self.config.order_amount;
+            } else if *level < current_price {
+                required_buy_coins += self.config.order_amount * level;
+            }
         }
 
         if balances[0] < required_sell_coins {
@@ -116,57 +265,61 @@ impl Gridbot {
         println!("\t{} ETH", balances[0]);
         println!("\t{} USDT", balances[1]);
 
-        // Place buy orders
-        for order_price in buy_orders {
-            let order = self
-                .client
-                .add_order(
-                    self.config.pair.as_str(),
-                    OrderType::LIMIT,
-                    OrderSide::BUY,
-                    self.config.order_amount,
-                    order_price,
-                )
-                .expect(format!("Failed to place buy order at {}", order_price).as_str());
-            println!("Placed buy order @ {} {}", order_price, coins[1]);
-            self.order_ids.push(order.order_id);
-        }
+        // Place one order per grid level straddling the current price.
+        let levels = self.levels.clone();
+        for (level_index, level) in levels.into_iter().enumerate() {
+            let side = if level > current_price {
+                OrderSide::SELL
+            } else if level < current_price {
+                OrderSide::BUY
+            } else {
+                continue;
+            };
 
-        // Place sell orders
-        for order_price in sell_orders {
-            let order = self
-                .client
-                .add_order(
-                    self.config.pair.as_str(),
-                    OrderType::LIMIT,
-                    OrderSide::SELL,
-                    self.config.order_amount,
-                    order_price,
-                )
-                .expect(format!("Failed to place sell order at {}", order_price).as_str());
-            println!("Placed sell order @ {} {}", order_price, coins[1]);
-            self.order_ids.push(order.order_id);
+            self.place_order(&symbol, level_index, side, level, self.config.order_amount)
+                .expect(format!("Failed to place {:?} order at {}", side, level).as_str());
         }
 
         Ok(())
     }
 
+    /// Polls every tracked order over REST. This is the fallback used to
+    /// reconcile state when the real-time stream (`run`) reconnects, since
+    /// it may have missed fills while disconnected.
     pub fn process(&mut self) -> Result<(), String> {
+        let symbol = self.client.get_symbol_info(self.config.pair.as_str())?;
+
         let mut to_remove: Vec<String> = vec![];
+        let mut to_place: Vec<(usize, OrderSide, Decimal, Decimal)> = vec![];
 
-        for id in &self.order_ids {
-            let order = self
+        for order in &self.orders {
+            let details = self
                 .client
-                .get_order_details(id.as_str())
+                .get_order_details(order.order_id.as_str())
                 .expect("Failed to get order details");
 
-            match order.status {
+            match details.status {
                 OrderStatus::filled => {
-                    println!("Order @ {} was filled", order.order_price);
-                    to_remove.push(id.clone());
+                    println!(
+                        "Order @ {} was filled: {} @ {}",
+                        details.order_price, details.executed, details.avg
+                    );
+                    to_remove.push(order.order_id.clone());
+
+                    if let Some((level_index, side, price)) =
+                        self.next_counter_order(order.side, order.level_index)
+                    {
+                        let already_queued = to_place
+                            .iter()
+                            .any(|&(idx, s, _, _)| idx == level_index && s == side);
+
+                        if !already_queued {
+                            to_place.push((level_index, side, price, details.executed));
+                        }
+                    }
                 }
                 OrderStatus::canceled => {
-                    to_remove.push(id.clone());
+                    to_remove.push(order.order_id.clone());
                 }
                 _ => {
                     // Don't do anything
@@ -175,7 +328,14 @@ impl Gridbot {
         }
 
         // Remove orders that no longer need monitoring
-        self.order_ids.retain(|x| !to_remove.contains(x));
+        self.orders.retain(|o| !to_remove.contains(&o.order_id));
+
+        // Place the counter-order for every fill detected above, sized off
+        // the realized fill rather than the configured order_amount.
+        for (level_index, side, price, size) in to_place {
+            self.place_order(&symbol, level_index, side, price, size)
+                .expect(format!("Failed to place {:?} counter-order at {}", side, price).as_str());
+        }
 
         Ok(())
     }